@@ -39,22 +39,75 @@ fn generate(args: DeriveInput) -> Result<TokenStream, GeneratorError> {
             ))?,
         };
 
-        fields.push((
-            ident.clone(),
-            field_data.rename.unwrap_or_else(|| ident.to_string()),
-        ))
+        let validate = field_data
+            .validate
+            .iter()
+            .map(|validate| syn::parse_str::<syn::Expr>(validate))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        fields.push(FieldPlan {
+            name: field_data.rename.unwrap_or_else(|| ident.to_string()),
+            ident,
+            ty: field_data.ty,
+            validate,
+        })
     }
 
-    let field_names = fields
+    let field_names = fields.iter().map(|field| field.ident.clone()).collect::<Vec<_>>();
+
+    let field_declarations = fields
         .iter()
-        .map(|(ident, _)| ident.clone())
+        .map(|field| {
+            let FieldPlan {
+                ident, name, ty, validate, ..
+            } = field;
+
+            let get = if is_tempfile_type(ty) {
+                quote::quote! {
+                    let #ident = map.get_temp_file(#name)?;
+                }
+            } else if is_vec_type(ty) && !is_vec_of_u8(ty) {
+                quote::quote! { let #ident = map.get_all(#name)?; }
+            } else {
+                quote::quote! { let #ident = map.get(#name)?; }
+            };
+
+            let validations = validate.iter().map(|validate| {
+                quote::quote! {
+                    if !(#validate)(&#ident) {
+                        return Err(poem::error::BadRequest(
+                            poem_typed_multipart::ValidationError(#name.to_string()),
+                        ));
+                    }
+                }
+            });
+
+            quote::quote! {
+                #get
+                #(#validations)*
+            }
+        })
         .collect::<Vec<_>>();
 
-    let field_declarations = fields
+    let tempfile_field_names = fields
         .iter()
-        .map(|(ident, name)| quote::quote! { let #ident = map.get(#name)?; })
+        .filter(|field| is_tempfile_type(&field.ty))
+        .map(|field| field.name.clone())
         .collect::<Vec<_>>();
 
+    // Only emitted when the struct has `TempFile` fields (which already require the `tempfile`
+    // feature): a `cfg` in the generated code would be checked against the deriving crate's
+    // features rather than this crate's.
+    let tempfile_fields_fn = if tempfile_field_names.is_empty() {
+        quote::quote! {}
+    } else {
+        quote::quote! {
+            fn tempfile_fields() -> &'static [&'static str] {
+                &[ #(#tempfile_field_names),* ]
+            }
+        }
+    };
+
     let stream = quote::quote! {
         impl poem_typed_multipart::FromMultiPart for #struct_ident {
             fn decode(map: poem_typed_multipart::map::MultiPartMap) -> Result<Self, poem::Error> {
@@ -62,12 +115,78 @@ fn generate(args: DeriveInput) -> Result<TokenStream, GeneratorError> {
 
                 Ok(Self { #(#field_names),* })
             }
+
+            #tempfile_fields_fn
         }
     };
 
     Ok(stream.into())
 }
 
+/// Everything the generator needs to know about a single struct field
+struct FieldPlan {
+    ident: Ident,
+    name: String,
+    ty: syn::Type,
+    /// Parsed `#[multipart(validate = "...")]` predicates, run in declaration order
+    validate: Vec<syn::Expr>,
+}
+
+/// Whether the given field type is a `Vec<_>`, used to detect fields that should collect every
+/// occurrence of a repeated multipart key instead of just the last one.
+///
+/// This is a syntactic check on the field's type rather than a `FromMultiPartPart` impl for
+/// `Vec<T>`, because collecting every occurrence needs access to every `RawPart` stored under a
+/// key, and `FromMultiPartPart` only ever sees the single part already selected for it. See
+/// `poem_typed_multipart::map::MultiPartMap::get_all` for the actual collection logic.
+fn is_vec_type(ty: &syn::Type) -> bool {
+    last_path_segment_is(ty, "Vec")
+}
+
+/// Whether the given field type is specifically `Vec<u8>`, which already has single-field
+/// "raw bytes" semantics via its own [`poem_typed_multipart::part::FromMultiPartPart`] impl and
+/// must not be routed through the repeated-occurrence `get_all` path like other `Vec<_>` fields.
+fn is_vec_of_u8(ty: &syn::Type) -> bool {
+    let syn::Type::Path(type_path) = ty else {
+        return false;
+    };
+
+    let Some(segment) = type_path.path.segments.last() else {
+        return false;
+    };
+
+    if segment.ident != "Vec" {
+        return false;
+    }
+
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return false;
+    };
+
+    matches!(
+        args.args.first(),
+        Some(syn::GenericArgument::Type(syn::Type::Path(inner)))
+            if inner.path.is_ident("u8")
+    ) && args.args.len() == 1
+}
+
+/// Whether the given field type is `TempFile`, used to detect fields that should be streamed
+/// straight to disk instead of buffered in memory.
+fn is_tempfile_type(ty: &syn::Type) -> bool {
+    last_path_segment_is(ty, "TempFile")
+}
+
+fn last_path_segment_is(ty: &syn::Type, name: &str) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name),
+        _ => false,
+    }
+}
+
 #[derive(Debug, FromDeriveInput)]
 #[darling(attributes(multipart), forward_attrs(doc))]
 struct FromMultiPartArgs {
@@ -79,8 +198,14 @@ struct FromMultiPartArgs {
 #[darling(attributes(multipart), forward_attrs(doc))]
 struct FromMultiPartData {
     ident: Option<Ident>,
+    ty: syn::Type,
     #[darling(default)]
     rename: Option<String>,
+    /// One or more `#[multipart(validate = "<expr>")]` predicates; `<expr>` is parsed as a
+    /// `Fn(&FieldType) -> bool` expression (a function path or closure) and called on the
+    /// decoded value, in the order the attributes are declared.
+    #[darling(default, multiple, rename = "validate")]
+    validate: Vec<String>,
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,71 @@
+//! Configuration controlling how large a multipart request is allowed to be
+
+use std::collections::HashMap;
+
+/// A size limit applied to a single field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldLimit {
+    /// Reject the request with `413 Payload Too Large` if the field exceeds this many bytes
+    Hard(usize),
+    /// Truncate the field to this many bytes instead of rejecting the request. Use
+    /// [`crate::part::Capped`] to find out whether a value was truncated. Fields streamed to a
+    /// [`crate::part::TempFile`] are not truncated: they are rejected as with [`FieldLimit::Hard`].
+    Soft(usize),
+}
+
+/// Configuration for [`crate::map::MultiPartMap`], bounding how much memory a multipart request
+/// is allowed to use.
+///
+/// Supply this as [`poem::Request`] data (e.g. via [`poem::EndpointExt::data`]) to apply it to
+/// [`crate::TypedMultiPart`] extractors on that route. Routes with no [`MultiPartConfig`] in
+/// their request data are left unrestricted.
+#[derive(Debug, Clone, Default)]
+pub struct MultiPartConfig {
+    total_limit: Option<usize>,
+    field_limits: HashMap<String, FieldLimit>,
+    #[cfg(feature = "tempfile")]
+    tempfile_dir: Option<std::path::PathBuf>,
+}
+
+impl MultiPartConfig {
+    /// Create a new [`MultiPartConfig`] with no limits configured
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject the request with `413 Payload Too Large` once the combined size of all fields
+    /// exceeds `limit` bytes
+    pub fn total_limit(mut self, limit: usize) -> Self {
+        self.total_limit = Some(limit);
+        self
+    }
+
+    /// Apply `limit` to the field named `name`
+    pub fn field_limit(mut self, name: impl Into<String>, limit: FieldLimit) -> Self {
+        self.field_limits.insert(name.into(), limit);
+        self
+    }
+
+    pub(crate) fn total_limit_bytes(&self) -> Option<usize> {
+        self.total_limit
+    }
+
+    pub(crate) fn field_limit_for(&self, name: &str) -> Option<FieldLimit> {
+        self.field_limits.get(name).copied()
+    }
+
+    /// Directory that [`crate::part::TempFile`] fields are streamed into. Defaults to
+    /// [`std::env::temp_dir`].
+    #[cfg(feature = "tempfile")]
+    pub fn tempfile_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.tempfile_dir = Some(dir.into());
+        self
+    }
+
+    #[cfg(feature = "tempfile")]
+    pub(crate) fn tempfile_dir_path(&self) -> std::path::PathBuf {
+        self.tempfile_dir
+            .clone()
+            .unwrap_or_else(std::env::temp_dir)
+    }
+}
@@ -32,25 +32,50 @@
 //!
 //! - `json`: Extract json values using the [`part::Json`] type.
 //! - `bytes`: Support for [`bytes::Bytes`].
+//! - `tempfile`: Stream large fields straight to disk using the [`part::TempFile`] type.
 
+use config::MultiPartConfig;
 use map::MultiPartMap;
 use poem::FromRequest;
 
 #[cfg(feature = "derive")]
 pub use poem_typed_multipart_macro::FromMultiPart;
 
+pub mod config;
 pub mod map;
 pub mod part;
 
 /// Trait implement indicating that the implement can be parsed from a multipart request
+///
+/// A struct field typed `Vec<T>` (`T: part::FromMultiPartPart`) other than `Vec<u8>` collects every
+/// occurrence of a repeated multipart key (`Vec<u8>` holds the raw bytes of a single occurrence);
+/// the `FromMultiPart` derive macro detects such fields and routes them
+/// through [`map::MultiPartMap::get_all`] rather than [`map::MultiPartMap::get`]. There is no
+/// `FromMultiPartPart` impl for `Vec<T>` itself: a [`part::FromMultiPartPart`] impl only ever sees
+/// the single part already selected for it and has no way to see sibling occurrences of the same
+/// key, so hand-written `FromMultiPart` implementations should call `get_all` directly for the
+/// same reason.
 pub trait FromMultiPart
 where
     Self: Sized,
 {
     /// Try to get the implementer based on the provided multipart request
     fn decode(map: MultiPartMap) -> Result<Self, poem::Error>;
+
+    /// Names of fields that should be streamed straight to disk instead of buffered in memory;
+    /// see [`part::TempFile`]. The `FromMultiPart` derive macro fills this in automatically for
+    /// any field typed [`part::TempFile`].
+    #[cfg(feature = "tempfile")]
+    fn tempfile_fields() -> &'static [&'static str] {
+        &[]
+    }
 }
 
+/// Error returned when a `#[multipart(validate = "...")]` predicate rejects a decoded field
+#[derive(Debug, thiserror::Error)]
+#[error("Field `{0}` failed validation")]
+pub struct ValidationError(pub String);
+
 /// Extractor used to get value `T` from the request. This consumes the request
 pub struct TypedMultiPart<T: FromMultiPart>(pub T);
 
@@ -60,7 +85,14 @@ impl<'a, T: FromMultiPart> FromRequest<'a> for TypedMultiPart<T> {
         body: &mut poem::RequestBody,
     ) -> Result<Self, poem::Error> {
         let body = poem::web::Multipart::from_request(req, body).await?;
-        let map = MultiPartMap::new(body).await?;
+        let config = req.data::<MultiPartConfig>().cloned().unwrap_or_default();
+        let map = MultiPartMap::new(
+            body,
+            &config,
+            #[cfg(feature = "tempfile")]
+            T::tempfile_fields(),
+        )
+        .await?;
 
         let value = T::decode(map)?;
 
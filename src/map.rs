@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use poem::http::StatusCode;
 use thiserror::Error;
 
+use crate::config::{FieldLimit, MultiPartConfig};
 use crate::part::FromMultiPartPart;
 
 /// Error that could occur when getting data from a [`MultiPartMap`]
@@ -20,42 +21,434 @@ impl<E> poem::error::ResponseError for MultiPartMapError<E> {
     }
 }
 
+/// Error that could occur while building a [`MultiPartMap`] from an incoming request
+#[derive(Debug, Error)]
+pub enum MultiPartBuildError {
+    #[error(transparent)]
+    Parse(#[from] poem::error::ParseMultipartError),
+    #[error("The field `{0}` exceeds the configured limit of {1} bytes")]
+    FieldTooLarge(String, usize),
+    #[error("The request body exceeds the configured limit of {0} bytes")]
+    TotalTooLarge(usize),
+    #[error("Failed to read field `{0}`: {1}")]
+    Io(String, #[source] std::io::Error),
+    #[cfg(feature = "tempfile")]
+    #[error("Failed to write field `{0}` to a temporary file: {1}")]
+    TempFileIo(String, #[source] std::io::Error),
+}
+
+impl poem::error::ResponseError for MultiPartBuildError {
+    fn status(&self) -> poem::http::StatusCode {
+        match self {
+            MultiPartBuildError::Parse(err) => err.status(),
+            MultiPartBuildError::FieldTooLarge(..) | MultiPartBuildError::TotalTooLarge(..) => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            MultiPartBuildError::Io(..) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "tempfile")]
+            MultiPartBuildError::TempFileIo(..) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Error returned by [`MultiPartMap::get_temp_file`] when the requested field was not streamed
+/// to a temporary file
+#[cfg(feature = "tempfile")]
+#[derive(Debug, Error)]
+#[error("The field `{0}` was not found in the request")]
+pub struct TempFileNotFoundError(String);
+
+/// Build a path for a new temporary file inside `dir`, unique for the lifetime of the process
+#[cfg(feature = "tempfile")]
+pub(crate) fn unique_temp_path(dir: &std::path::Path) -> std::path::PathBuf {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+
+    dir.join(format!("poem-typed-multipart-{nanos}-{id}"))
+}
+
+/// Read a field's body in bounded-size chunks, checking `field_limit` and the remaining budget
+/// under `total_limit` as each chunk arrives. This lets us reject an oversized field (or request)
+/// as soon as the limit is crossed, instead of buffering the whole field in memory first.
+async fn read_limited_field(
+    field: poem::web::Field,
+    name: &str,
+    field_limit: Option<FieldLimit>,
+    total_limit: Option<usize>,
+    total_size: &mut usize,
+) -> Result<(Vec<u8>, bool), MultiPartBuildError> {
+    use tokio::io::AsyncReadExt;
+
+    let hard_limit = match field_limit {
+        Some(FieldLimit::Hard(limit)) => Some(limit),
+        _ => None,
+    };
+    let soft_limit = match field_limit {
+        Some(FieldLimit::Soft(limit)) => Some(limit),
+        _ => None,
+    };
+
+    let mut reader = field.into_async_read();
+    let mut chunk = [0u8; 8192];
+    let mut buffer = Vec::new();
+    let mut field_size = 0usize;
+    let mut truncated = false;
+
+    loop {
+        let read = reader
+            .read(&mut chunk)
+            .await
+            .map_err(|err| MultiPartBuildError::Io(name.to_string(), err))?;
+        if read == 0 {
+            break;
+        }
+        field_size += read;
+
+        if let Some(limit) = hard_limit {
+            if field_size > limit {
+                return Err(MultiPartBuildError::FieldTooLarge(name.to_string(), limit));
+            }
+        }
+        if let Some(limit) = total_limit {
+            if *total_size + field_size > limit {
+                return Err(MultiPartBuildError::TotalTooLarge(limit));
+            }
+        }
+
+        // Once a soft limit is hit we keep draining the field (so the multipart parser stays in
+        // sync for the next field) but stop growing the buffer past the cap.
+        let room = soft_limit.map_or(read, |limit| limit.saturating_sub(buffer.len()).min(read));
+        if room < read {
+            truncated = true;
+        }
+        buffer.extend_from_slice(&chunk[..room]);
+    }
+
+    *total_size += field_size;
+
+    Ok((buffer, truncated))
+}
+
+/// Stream a field straight to a new file inside `dir`, in the same bounded chunks as
+/// [`read_limited_field`], checking `field_limit` and the remaining budget under `total_limit` as
+/// each chunk arrives. Returns the path of the file on success; on error the partially written
+/// file is removed before returning.
+///
+/// [`crate::part::TempFile`] has no notion of truncation, so unlike [`read_limited_field`], both
+/// [`FieldLimit::Hard`] and [`FieldLimit::Soft`] are enforced as a hard cap here.
+#[cfg(feature = "tempfile")]
+async fn stream_field_to_tempfile(
+    field: poem::web::Field,
+    name: &str,
+    field_limit: Option<FieldLimit>,
+    total_limit: Option<usize>,
+    total_size: &mut usize,
+    dir: &std::path::Path,
+) -> Result<std::path::PathBuf, MultiPartBuildError> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let hard_limit = field_limit.map(|limit| match limit {
+        FieldLimit::Hard(limit) | FieldLimit::Soft(limit) => limit,
+    });
+
+    let path = unique_temp_path(dir);
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .map_err(|err| MultiPartBuildError::TempFileIo(name.to_string(), err))?;
+
+    let mut reader = field.into_async_read();
+    let mut chunk = [0u8; 8192];
+    let mut field_size = 0usize;
+
+    let result = async {
+        loop {
+            let read = reader
+                .read(&mut chunk)
+                .await
+                .map_err(|err| MultiPartBuildError::TempFileIo(name.to_string(), err))?;
+            if read == 0 {
+                break;
+            }
+            field_size += read;
+
+            if let Some(limit) = hard_limit {
+                if field_size > limit {
+                    return Err(MultiPartBuildError::FieldTooLarge(name.to_string(), limit));
+                }
+            }
+            if let Some(limit) = total_limit {
+                if *total_size + field_size > limit {
+                    return Err(MultiPartBuildError::TotalTooLarge(limit));
+                }
+            }
+
+            file.write_all(&chunk[..read])
+                .await
+                .map_err(|err| MultiPartBuildError::TempFileIo(name.to_string(), err))?;
+        }
+
+        Ok(())
+    }
+    .await;
+
+    if let Err(err) = result {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(err);
+    }
+
+    *total_size += field_size;
+
+    Ok(path)
+}
+
+/// The raw, not-yet-decoded data captured for a single occurrence of a multipart field, together
+/// with the metadata carried by its `Content-Disposition`/`Content-Type` headers.
+#[derive(Debug, Clone)]
+pub struct RawPart {
+    bytes: Vec<u8>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+    truncated: bool,
+}
+
+impl RawPart {
+    /// The raw bytes of the field
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The file name declared in the field's `Content-Disposition` header, if any
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The MIME type declared in the field's `Content-Type` header, if any
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Whether the field was truncated to fit within a configured [`FieldLimit::Soft`] limit
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
 /// A map containing a parsed multipart request
 #[derive(Debug)]
 pub struct MultiPartMap {
-    map: HashMap<String, Vec<u8>>,
+    map: HashMap<String, Vec<RawPart>>,
+    #[cfg(feature = "tempfile")]
+    temp_files: HashMap<String, Vec<crate::part::TempFile>>,
 }
 
 impl MultiPartMap {
-    /// Create a new [`MultiPartMap`]
+    /// Create a new [`MultiPartMap`], enforcing the limits set by `config`. Fields named in
+    /// `tempfile_fields` are streamed straight to disk instead of being buffered in `map`; see
+    /// [`MultiPartMap::get_temp_file`].
     pub(crate) async fn new(
         mut body: poem::web::Multipart,
-    ) -> Result<Self, poem::error::ParseMultipartError> {
-        let mut map = HashMap::new();
+        config: &MultiPartConfig,
+        #[cfg(feature = "tempfile")] tempfile_fields: &[&str],
+    ) -> Result<Self, MultiPartBuildError> {
+        let mut map: HashMap<String, Vec<RawPart>> = HashMap::new();
+        #[cfg(feature = "tempfile")]
+        let mut temp_files: HashMap<String, Vec<crate::part::TempFile>> = HashMap::new();
+        let mut total_size = 0usize;
 
         while let Ok(Some(field)) = body.next_field().await {
             let name = match field.name() {
                 Some(name) => name.to_string(),
                 None => continue,
             };
+            let file_name = field.file_name().map(ToString::to_string);
+            let content_type = field.content_type().map(ToString::to_string);
 
-            let value = field.bytes().await?;
+            #[cfg(feature = "tempfile")]
+            if tempfile_fields.contains(&name.as_str()) {
+                let path = stream_field_to_tempfile(
+                    field,
+                    &name,
+                    config.field_limit_for(&name),
+                    config.total_limit_bytes(),
+                    &mut total_size,
+                    &config.tempfile_dir_path(),
+                )
+                .await?;
 
-            map.insert(name, value);
+                temp_files.entry(name).or_default().push(crate::part::TempFile::new(
+                    path,
+                    file_name,
+                    content_type,
+                ));
+                continue;
+            }
+
+            let (bytes, truncated) = read_limited_field(
+                field,
+                &name,
+                config.field_limit_for(&name),
+                config.total_limit_bytes(),
+                &mut total_size,
+            )
+            .await?;
+
+            map.entry(name).or_default().push(RawPart {
+                bytes,
+                file_name,
+                content_type,
+                truncated,
+            });
         }
 
-        Ok(Self { map })
+        Ok(Self {
+            map,
+            #[cfg(feature = "tempfile")]
+            temp_files,
+        })
     }
 
     /// Get the value behind the key in the multipart map. Returns a [`MultiPartMapError`] is the
     /// key could not be found in the map, or the value in behind the key could not be decoded.
+    ///
+    /// If the request contained the key multiple times, the last occurrence is used. Use
+    /// [`MultiPartMap::get_all`] to collect every occurrence instead.
     pub fn get<'a, S: FromMultiPartPart<'a>>(&'a self, key: &str) -> Result<S, poem::Error> {
-        let value = if let Some(value) = self.map.get(key) {
-            S::from_bytes(value).map_err(poem::error::BadRequest)?
+        let value = if let Some(part) = self.map.get(key).and_then(|values| values.last()) {
+            S::from_part(part).map_err(poem::error::BadRequest)?
         } else {
             S::handle_absent_value(key)?
         };
 
         Ok(value)
     }
+
+    /// Get every value behind the key in the multipart map, decoding each occurrence
+    /// individually. Unlike [`MultiPartMap::get`], an absent key yields an empty [`Vec`] rather
+    /// than an error, since sending a field zero times is indistinguishable from an empty
+    /// collection.
+    ///
+    /// There is deliberately no `impl<T: FromMultiPartPart> FromMultiPartPart for Vec<T>`: a
+    /// [`FromMultiPartPart`] impl only ever sees the single [`RawPart`] already selected for it,
+    /// with no visibility into sibling occurrences of the same key, so it cannot implement
+    /// "collect every occurrence" itself. Struct fields typed `Vec<T>` (other than `Vec<u8>`, which
+    /// goes through [`MultiPartMap::get`] as raw bytes) are instead routed to this
+    /// method directly by the `FromMultiPart` derive macro; hand-written [`crate::FromMultiPart`]
+    /// impls should call `get_all` the same way. This also means `Vec<T>` cannot be nested inside
+    /// another [`FromMultiPartPart`] wrapper such as `Option<_>` or [`crate::part::Capped`].
+    pub fn get_all<'a, S: FromMultiPartPart<'a>>(&'a self, key: &str) -> Result<Vec<S>, poem::Error> {
+        self.map
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|part| S::from_part(part).map_err(poem::error::BadRequest))
+            .collect()
+    }
+
+    /// Get the [`crate::part::TempFile`] streamed for `key`. Unlike [`MultiPartMap::get`], this
+    /// does not go through [`FromMultiPartPart`]: it only returns a field that was actually
+    /// streamed to disk, i.e. one listed in the `tempfile_fields` this map was built with. The
+    /// `FromMultiPart` derive macro calls this automatically for fields typed
+    /// [`crate::part::TempFile`].
+    #[cfg(feature = "tempfile")]
+    pub fn get_temp_file(&self, key: &str) -> Result<crate::part::TempFile, poem::Error> {
+        self.temp_files
+            .get(key)
+            .and_then(|files| files.last())
+            .cloned()
+            .ok_or_else(|| poem::error::BadRequest(TempFileNotFoundError(key.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poem::{
+        handler,
+        http::StatusCode,
+        test::{TestClient, TestForm},
+        web::Data,
+    };
+
+    use super::MultiPartMap;
+    use crate::{
+        config::{FieldLimit, MultiPartConfig},
+        part::Capped,
+    };
+
+    #[handler]
+    async fn build_map(
+        multipart: poem::web::Multipart,
+        Data(config): Data<&MultiPartConfig>,
+    ) -> poem::Result<poem::Response> {
+        let map = MultiPartMap::new(
+            multipart,
+            config,
+            #[cfg(feature = "tempfile")]
+            &[],
+        )
+        .await?;
+
+        let field = map.get::<Capped<Vec<u8>>>("file")?;
+
+        Ok(poem::Response::builder()
+            .header("x-complete", field.is_complete().to_string())
+            .body(field.into_inner()))
+    }
+
+    #[tokio::test]
+    async fn rejects_field_over_hard_limit() {
+        let cli = TestClient::new(build_map);
+        let config = MultiPartConfig::new().field_limit("file", FieldLimit::Hard(4));
+
+        let resp = cli
+            .post("/")
+            .data(config)
+            .multipart(TestForm::new().bytes("file", b"too much data".to_vec()))
+            .send()
+            .await;
+
+        resp.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn truncates_field_over_soft_limit() {
+        let cli = TestClient::new(build_map);
+        let config = MultiPartConfig::new().field_limit("file", FieldLimit::Soft(5));
+
+        let resp = cli
+            .post("/")
+            .data(config)
+            .multipart(TestForm::new().bytes("file", b"hello world".to_vec()))
+            .send()
+            .await;
+
+        resp.assert_status_is_ok();
+        resp.assert_header("x-complete", "false");
+        resp.assert_bytes(b"hello".to_vec()).await;
+    }
+
+    #[tokio::test]
+    async fn rejects_request_over_total_limit_spanning_fields() {
+        let cli = TestClient::new(build_map);
+        let config = MultiPartConfig::new().total_limit(10);
+
+        let resp = cli
+            .post("/")
+            .data(config)
+            .multipart(
+                TestForm::new()
+                    .bytes("file", b"123456".to_vec())
+                    .bytes("other", b"123456".to_vec()),
+            )
+            .send()
+            .await;
+
+        resp.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
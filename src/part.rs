@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::map::RawPart;
+
 /// Default error returned whenever a value is not absent in a multipart part
 #[derive(Debug, Error)]
 #[error("The field `{0}` was not found in the request")]
@@ -19,6 +21,25 @@ where
     /// Convert the provided byte blob into the part
     fn from_bytes(body: &'a [u8]) -> Result<Self, Self::Error>;
 
+    /// Like [`FromMultiPartPart::from_bytes`], but with access to the field's declared content
+    /// type. The default implementation ignores the content type and defers to
+    /// [`FromMultiPartPart::from_bytes`]; override it to dispatch or reject based on the
+    /// declared MIME type, as [`Json`] does.
+    fn from_bytes_with_content_type(
+        body: &'a [u8],
+        _content_type: Option<&str>,
+    ) -> Result<Self, Self::Error> {
+        Self::from_bytes(body)
+    }
+
+    /// Convert the provided part into the value, with access to the field's declared file name
+    /// and content type in addition to its bytes. The default implementation defers to
+    /// [`FromMultiPartPart::from_bytes_with_content_type`]; override it when the file name
+    /// matters too, as [`UploadField`] does.
+    fn from_part(part: &'a RawPart) -> Result<Self, Self::Error> {
+        Self::from_bytes_with_content_type(part.bytes(), part.content_type())
+    }
+
     /// Handler called when the value is absent from the request. A recovery strategy should be
     /// provided here if relevant.
     fn handle_absent_value(key: &str) -> Result<Self, poem::Error> {
@@ -171,17 +192,225 @@ impl FromMultiPartPart<'_> for bytes::Bytes {
     }
 }
 
+/// A multipart field captured alongside its declared file name and content type, for endpoints
+/// that need more than just the raw bytes (e.g. to branch on the uploaded file's MIME type).
+#[derive(Debug, Clone)]
+pub struct UploadField {
+    bytes: Vec<u8>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+impl UploadField {
+    /// The raw bytes of the field
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The file name declared in the field's `Content-Disposition` header, if any
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The MIME type declared in the field's `Content-Type` header, if any
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+}
+
+/// A value decoded from a field that may have been truncated to fit within a configured
+/// [`crate::config::FieldLimit::Soft`] limit. Check [`Capped::is_complete`] before trusting the
+/// value to be whole, e.g. for anything that must round-trip (JSON, checksums, …).
+#[derive(Debug, Clone)]
+pub struct Capped<T> {
+    value: T,
+    complete: bool,
+}
+
+impl<T> Capped<T> {
+    /// The decoded value, whether or not it was truncated
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Unwrap into the decoded value, whether or not it was truncated
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Whether the field was read in full, rather than truncated to fit the configured limit
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+impl<'a, T: FromMultiPartPart<'a>> FromMultiPartPart<'a> for Capped<T> {
+    type Error = T::Error;
+
+    fn from_bytes(body: &'a [u8]) -> Result<Self, Self::Error> {
+        T::from_bytes(body).map(|value| Capped {
+            value,
+            complete: true,
+        })
+    }
+
+    fn from_bytes_with_content_type(
+        body: &'a [u8],
+        content_type: Option<&str>,
+    ) -> Result<Self, Self::Error> {
+        T::from_bytes_with_content_type(body, content_type).map(|value| Capped {
+            value,
+            complete: true,
+        })
+    }
+
+    fn from_part(part: &'a RawPart) -> Result<Self, Self::Error> {
+        T::from_part(part).map(|value| Capped {
+            value,
+            complete: !part.is_truncated(),
+        })
+    }
+}
+
+impl<'a> FromMultiPartPart<'a> for UploadField {
+    type Error = std::convert::Infallible;
+
+    fn from_bytes(body: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(UploadField {
+            bytes: body.to_vec(),
+            file_name: None,
+            content_type: None,
+        })
+    }
+
+    fn from_part(part: &'a RawPart) -> Result<Self, Self::Error> {
+        Ok(UploadField {
+            bytes: part.bytes().to_vec(),
+            file_name: part.file_name().map(ToString::to_string),
+            content_type: part.content_type().map(ToString::to_string),
+        })
+    }
+}
+
+/// A multipart field streamed straight to a temporary file instead of being buffered in memory,
+/// for large uploads (e.g. file inputs). Obtained without ever materializing the field's bytes
+/// in RAM when used through the `FromMultiPart` derive macro, which streams the field to disk as
+/// it arrives; see [`crate::map::MultiPartMap::get_temp_file`]. Configure where streamed fields
+/// are written with [`crate::config::MultiPartConfig::tempfile_dir`].
+#[cfg(feature = "tempfile")]
+#[derive(Debug, Clone)]
+pub struct TempFile {
+    path: std::path::PathBuf,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+#[cfg(feature = "tempfile")]
+impl TempFile {
+    pub(crate) fn new(
+        path: std::path::PathBuf,
+        file_name: Option<String>,
+        content_type: Option<String>,
+    ) -> Self {
+        Self {
+            path,
+            file_name,
+            content_type,
+        }
+    }
+
+    /// The path of the temporary file on disk
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    /// The file name declared in the field's `Content-Disposition` header, if any
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// The MIME type declared in the field's `Content-Type` header, if any
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Move the temporary file to `dest`
+    pub async fn persist(self, dest: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        tokio::fs::rename(&self.path, dest).await
+    }
+}
+
+#[cfg(feature = "tempfile")]
+impl<'a> FromMultiPartPart<'a> for TempFile {
+    type Error = std::io::Error;
+
+    /// Writes the already-buffered bytes out to a fresh file in [`std::env::temp_dir`]; the
+    /// configured [`crate::config::MultiPartConfig::tempfile_dir`] does not apply. Prefer declaring the
+    /// field as [`TempFile`] on a `FromMultiPart` derive, which streams it to disk directly
+    /// through [`crate::map::MultiPartMap::get_temp_file`] instead of buffering it first.
+    fn from_bytes(body: &'a [u8]) -> Result<Self, Self::Error> {
+        let path = crate::map::unique_temp_path(&std::env::temp_dir());
+        std::fs::write(&path, body)?;
+
+        Ok(TempFile {
+            path,
+            file_name: None,
+            content_type: None,
+        })
+    }
+
+    fn from_part(part: &'a RawPart) -> Result<Self, Self::Error> {
+        let path = crate::map::unique_temp_path(&std::env::temp_dir());
+        std::fs::write(&path, part.bytes())?;
+
+        Ok(TempFile {
+            path,
+            file_name: part.file_name().map(ToString::to_string),
+            content_type: part.content_type().map(ToString::to_string),
+        })
+    }
+}
+
 #[cfg(feature = "json")]
 #[derive(Debug)]
 /// Newtype around a value with [`serde::Deserialize`] used to deserialize json values
 pub struct Json<T>(pub T);
 
+/// Errors that could occur while deserializing a [`Json`] part
+#[cfg(feature = "json")]
+#[derive(Debug, Error)]
+pub enum JsonError {
+    #[error("Failed to parse the field as JSON: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("Expected content type `application/json`, got `{0}`")]
+    UnexpectedContentType(String),
+}
+
 #[cfg(feature = "json")]
 impl<'de, 'a: 'de, T: serde::Deserialize<'de>> FromMultiPartPart<'a> for Json<T> {
-    type Error = serde_json::Error;
+    type Error = JsonError;
 
     fn from_bytes(body: &'a [u8]) -> Result<Json<T>, Self::Error> {
         let data = serde_json::from_slice(body)?;
         Ok(Json(data))
     }
+
+    fn from_bytes_with_content_type(
+        body: &'a [u8],
+        content_type: Option<&str>,
+    ) -> Result<Json<T>, Self::Error> {
+        if let Some(content_type) = content_type {
+            let mime = content_type
+                .split(';')
+                .next()
+                .unwrap_or(content_type)
+                .trim();
+
+            if !mime.eq_ignore_ascii_case("application/json") {
+                return Err(JsonError::UnexpectedContentType(content_type.to_string()));
+            }
+        }
+
+        Self::from_bytes(body)
+    }
 }